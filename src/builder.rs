@@ -0,0 +1,94 @@
+use std::any::Any;
+use std::time::Duration;
+
+use keyring::credential::{Credential, CredentialBuilderApi};
+use keyring::Result;
+
+use crate::credentials::{KeyType, KeyringAnchor, KeyutilsCredential};
+
+/// Builder for keyutils credentials: controls which special keyring newly built
+/// [`KeyutilsCredential`]s are anchored to, what default TTL they're given, and which key type
+/// backs them.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyutilsCredentialBuilder {
+    anchor: KeyringAnchor,
+    default_ttl: Option<Duration>,
+    key_type: Option<KeyType>,
+    target_uid: Option<u32>,
+}
+
+impl KeyutilsCredentialBuilder {
+    /// Create a new builder. Entries built from it are anchored to the session keyring, have no
+    /// default TTL, auto-select their key type by payload size, and (for
+    /// [`KeyringAnchor::Persistent`]) use the caller's own UID, matching this crate's historical
+    /// default.
+    pub fn new() -> Self {
+        Self {
+            anchor: KeyringAnchor::Session,
+            default_ttl: None,
+            key_type: None,
+            target_uid: None,
+        }
+    }
+
+    /// Build entries anchored to `anchor` instead of the session keyring.
+    ///
+    /// For example, a cron-driven tool that needs its credential to survive logout should use
+    /// [`KeyringAnchor::Persistent`], while a short-lived helper that shouldn't leak its secret
+    /// to children can use [`KeyringAnchor::Process`] or [`KeyringAnchor::Thread`].
+    pub fn with_anchor(mut self, anchor: KeyringAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Give entries built from this builder a default expiration timeout, applied every time
+    /// their secret is written. See [`KeyutilsCredential::get_password_or_refresh`] for pairing
+    /// this with automatic refresh on expiry.
+    pub fn with_default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = Some(ttl);
+        self
+    }
+
+    /// Force entries built from this builder to use `key_type`, instead of auto-selecting
+    /// `user` or `big_key` based on the secret's size.
+    pub fn with_key_type(mut self, key_type: KeyType) -> Self {
+        self.key_type = Some(key_type);
+        self
+    }
+
+    /// For entries anchored to [`KeyringAnchor::Persistent`], use `uid`'s persistent keyring.
+    ///
+    /// `linux-keyutils`'s `get_persistent` has no cross-UID variant, so this only actually works
+    /// when `uid` is the caller's own real UID (a no-op - equivalent to never calling this at
+    /// all); any other UID always fails with a clear error, rather than silently falling back to
+    /// the caller's own persistent keyring, when the entry is used. Ignored for every other
+    /// anchor.
+    pub fn with_target_uid(mut self, uid: u32) -> Self {
+        self.target_uid = Some(uid);
+        self
+    }
+}
+
+impl Default for KeyutilsCredentialBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CredentialBuilderApi for KeyutilsCredentialBuilder {
+    fn build(&self, target: Option<&str>, service: &str, user: &str) -> Result<Box<Credential>> {
+        Ok(Box::new(KeyutilsCredential::new_full(
+            target,
+            service,
+            user,
+            self.anchor,
+            self.default_ttl,
+            self.key_type,
+            self.target_uid,
+        )?))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,120 @@
+use keyring::Result;
+use linux_keyutils::KeyRing;
+
+use crate::credentials::{open_anchor, KeyringAnchor};
+use crate::error::{decode_error, read_links};
+
+/// A group of related credentials, addressable and enumerable as a unit.
+///
+/// This models the per-realm/per-host credential groups callers often want to manage together
+/// (Kerberos tickets, host tokens) - where a [`KeyutilsCredential`](crate::KeyutilsCredential)
+/// addresses exactly one flat `description`, a `KeyutilsCollection` groups many of them.
+///
+/// `linux-keyutils` has no public API for creating or finding a *named child keyring* (its
+/// `add_key`/`search` only ever operate on one of the kernel's special keyrings), so this can't
+/// be a true keyring-of-keyrings the way `keyctl newring` would build one. Instead, every entry
+/// in a collection is stored directly on the anchor keyring with its description namespaced
+/// under the collection's name, and `entries`/`delete` walk the anchor's links to find them.
+///
+/// Two things follow from that, and callers should plan for both: enumeration shares the same
+/// keyring as every other `KeyutilsCollection` (and every plain [`KeyutilsCredential`]) anchored
+/// there, so a collision between this collection's name and another credential's raw description
+/// is possible in principle, however unlikely in practice; and [`delete`](Self::delete) unlinks
+/// its entries one at a time rather than dropping a subtree atomically, so a failure partway
+/// through can leave some entries removed and others still present.
+pub struct KeyutilsCollection {
+    anchor: KeyringAnchor,
+    name: String,
+}
+
+impl KeyutilsCollection {
+    /// Open the collection named `name`, anchored to `anchor`. Collections are implicit - there's
+    /// nothing to create - so this never fails due to the collection itself not existing yet.
+    pub fn new(anchor: KeyringAnchor, name: &str) -> Result<Self> {
+        // Make sure the anchor keyring itself is reachable, so callers find out about permission
+        // problems here rather than on the first `add`.
+        open_anchor(anchor)?;
+        Ok(Self {
+            anchor,
+            name: name.to_string(),
+        })
+    }
+
+    fn keyring(&self) -> Result<KeyRing> {
+        open_anchor(self.anchor)
+    }
+
+    /// The namespaced description backing `description` within this collection.
+    fn namespaced(&self, description: &str) -> String {
+        format!("collection:{}/{description}", self.name)
+    }
+
+    /// Add (or overwrite) a credential named `description` within this collection.
+    pub fn add(&self, description: &str, secret: &[u8]) -> Result<()> {
+        self.keyring()?
+            .add_key(&self.namespaced(description), secret)
+            .map_err(decode_error)?;
+        Ok(())
+    }
+
+    /// Fetch the secret stored under `description` within this collection.
+    pub fn get(&self, description: &str) -> Result<Vec<u8>> {
+        let key = self
+            .keyring()?
+            .search(&self.namespaced(description))
+            .map_err(decode_error)?;
+        key.read_to_vec().map_err(decode_error)
+    }
+
+    /// Remove a single credential named `description` from this collection, leaving the rest
+    /// of the collection intact.
+    pub fn remove(&self, description: &str) -> Result<()> {
+        let keyring = self.keyring()?;
+        let key = keyring
+            .search(&self.namespaced(description))
+            .map_err(decode_error)?;
+        keyring.unlink_key(key).map_err(decode_error)
+    }
+
+    /// List the (un-namespaced) descriptions of every credential currently in this collection.
+    pub fn entries(&self) -> Result<Vec<String>> {
+        let prefix = format!("collection:{}/", self.name);
+        Ok(self
+            .links()?
+            .into_iter()
+            .filter_map(|description| description.strip_prefix(&prefix).map(str::to_string))
+            .collect())
+    }
+
+    /// Drop every credential in this collection.
+    ///
+    /// This unlinks entries one at a time rather than dropping a subtree in one kernel call (see
+    /// the struct docs), so a failure partway through can leave some entries deleted and others
+    /// still present; callers that need an all-or-nothing guarantee should call [`entries`](Self::entries)
+    /// first and handle a partial failure themselves.
+    pub fn delete(self) -> Result<()> {
+        let keyring = self.keyring()?;
+        for description in self.entries()? {
+            let key = keyring
+                .search(&self.namespaced(&description))
+                .map_err(decode_error)?;
+            keyring.unlink_key(key).map_err(decode_error)?;
+        }
+        Ok(())
+    }
+
+    /// The descriptions of every key directly linked into this collection's anchor keyring.
+    fn links(&self) -> Result<Vec<String>> {
+        let keyring = self.keyring()?;
+        let links = read_links(self.anchor, &keyring)?;
+        links
+            .iter()
+            .filter_map(|node| node.as_key())
+            .map(|key| {
+                key.metadata()
+                    .map_err(decode_error)
+                    .map(|metadata| metadata.get_description().to_string())
+            })
+            .collect()
+    }
+}
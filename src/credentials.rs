@@ -0,0 +1,502 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::time::Duration;
+
+use keyring::credential::{Credential, CredentialApi};
+use keyring::{Error as ErrorCode, Result};
+use linux_keyutils::{Key, KeyError, KeyRing, KeyRingIdentifier, KeySerialId, KeyType as RawKeyType};
+
+use crate::error::decode_error;
+
+/// The kernel's `maxbytes` quota for a single `user`-type key defaults to 32767 bytes; payloads
+/// larger than this need the `big_key` type instead. See
+/// [keyrings(7)](https://man7.org/linux/man-pages/man7/keyrings.7.html).
+const USER_KEY_MAX_SIZE: usize = 32_767;
+
+/// The kernel's payload limit for a `big_key`-type key. See
+/// [keyrings(7)](https://man7.org/linux/man-pages/man7/keyrings.7.html).
+const BIG_KEY_MAX_SIZE: usize = 1_048_576;
+
+/// `KEYCTL_SEARCH`, from `man 2 keyctl`. `linux-keyutils` has its own copy of this opcode, but
+/// it's not public, and its `KeyRing::search` is hardcoded to the `user` key type - so looking up
+/// a `big_key` has to go through this raw opcode directly instead.
+const KEYCTL_SEARCH: libc::c_int = 10;
+
+/// Which kernel key type backs a [`KeyutilsCredential`]'s secret.
+///
+/// `user` keys are held entirely in kernel memory, which caps their payload at
+/// [`USER_KEY_MAX_SIZE`]; `big_key` keys can spill to encrypted tmpfs and hold much larger
+/// payloads (Kerberos ccache blobs, long JWT/OAuth bundles, ...), up to [`BIG_KEY_MAX_SIZE`].
+///
+/// `linux-keyutils`'s `KeyRing::add_key`/`search` are hardcoded to the `user` type - it has no
+/// public API for creating or finding a `big_key` - so both are written and looked up here with
+/// this crate's own raw `add_key(2)`/`keyctl(2)` syscalls instead, built from the same public
+/// pieces (`KeyType`'s `CStr` conversion, `KeyError::from_errno`) `linux-keyutils` itself uses
+/// internally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyType {
+    /// The default `user` key type: small payloads, held in kernel memory.
+    User,
+    /// The `big_key` key type: large payloads, backed by encrypted tmpfs.
+    ///
+    /// Not supported for [`KeyringAnchor::Persistent`]: `linux-keyutils`'s `get_persistent`
+    /// returns a [`KeyRing`] whose real serial number is private with no public getter, so
+    /// there's no way to target it with a raw syscall. A `big_key` write or lookup against a
+    /// `Persistent`-anchored credential fails with [`ErrorCode::Invalid`].
+    BigKey,
+}
+
+impl KeyType {
+    /// Pick `user` or `big_key` automatically based on how large `secret` is.
+    fn for_secret(secret: &[u8]) -> Self {
+        if secret.len() > USER_KEY_MAX_SIZE {
+            KeyType::BigKey
+        } else {
+            KeyType::User
+        }
+    }
+}
+
+/// Create or update a key of `ktype` on the special keyring `target`, returning its handle.
+///
+/// Mirrors `linux-keyutils`'s own (private) `ffi::add_key`, the helper its hardcoded-to-`user`
+/// `KeyRing::add_key` calls internally.
+fn add_key_of_type(
+    target: libc::c_ulong,
+    ktype: RawKeyType,
+    description: &str,
+    payload: &[u8],
+) -> std::result::Result<Key, KeyError> {
+    let description = CString::new(description).or(Err(KeyError::InvalidDescription))?;
+    let res = unsafe {
+        libc::syscall(
+            libc::SYS_add_key,
+            Into::<&'static std::ffi::CStr>::into(ktype).as_ptr(),
+            description.as_ptr(),
+            payload.as_ptr(),
+            payload.len() as libc::size_t,
+            target as u32,
+        )
+    };
+    if res < 0 {
+        return Err(KeyError::from_errno());
+    }
+    Ok(Key::from_id(KeySerialId::new(
+        res.try_into().or(Err(KeyError::InvalidIdentifier))?,
+    )))
+}
+
+/// Search for a key of `ktype` from the special keyring `target`, returning its handle.
+///
+/// Mirrors `linux-keyutils`'s own (private) `KeyCtlOperation::Search` handling - used internally
+/// only by the hardcoded-to-`user` `KeyRing::search` - via the raw `KEYCTL_SEARCH` opcode, since
+/// that operation enum isn't public either.
+fn search_of_type(
+    target: libc::c_ulong,
+    ktype: RawKeyType,
+    description: &str,
+) -> std::result::Result<Key, KeyError> {
+    let description = CString::new(description).or(Err(KeyError::InvalidDescription))?;
+    let res = unsafe {
+        libc::syscall(
+            libc::SYS_keyctl,
+            KEYCTL_SEARCH,
+            target,
+            Into::<&'static std::ffi::CStr>::into(ktype).as_ptr(),
+            description.as_ptr(),
+            0,
+        )
+    };
+    if res < 0 {
+        return Err(KeyError::from_errno());
+    }
+    Ok(Key::from_id(KeySerialId::new(
+        res.try_into().or(Err(KeyError::InvalidIdentifier))?,
+    )))
+}
+
+/// Where a directly-addressable `big_key` is written to/searched from: one of the kernel's
+/// special keyring IDs, which `add_key(2)`/`keyctl(2)` accept directly without first opening a
+/// [`KeyRing`] handle.
+///
+/// [`KeyringAnchor::Persistent`] has no such ID available: `KeyRing::get_persistent` resolves
+/// the persistent keyring's real serial number internally and never exposes it (the `KeyRing`
+/// struct's `id` field is private, with no public getter anywhere in `linux-keyutils`), so
+/// there's no way to hand it to a raw syscall. This is a hard limitation of the dependency, not
+/// a choice this crate is making.
+fn big_key_target(anchor: KeyringAnchor) -> Result<libc::c_ulong> {
+    if anchor == KeyringAnchor::Persistent {
+        return Err(ErrorCode::Invalid(
+            "anchor".to_string(),
+            "big_key secrets aren't supported for KeyringAnchor::Persistent: linux-keyutils \
+             never exposes its persistent keyring's real serial number, so there's no way to \
+             target it with a raw big_key syscall"
+                .to_string(),
+        ));
+    }
+    Ok(anchor.special_id() as libc::c_ulong)
+}
+
+/// Convert `ttl` to the whole seconds `Key::set_timeout` expects, rounding any sub-second
+/// duration up to 1 rather than down to 0: `set_timeout(0)` doesn't mean "expire immediately", it
+/// *clears* the key's timeout entirely, which would silently turn a short TTL into no expiry at
+/// all.
+fn timeout_secs(ttl: Duration) -> usize {
+    ttl.as_secs().max(1) as usize
+}
+
+/// The special keyring that a [`KeyutilsCredential`] is linked into and searched from.
+///
+/// Each variant corresponds to one of the kernel's "special" keyrings (see
+/// [keyrings(7)](https://man7.org/linux/man-pages/man7/keyrings.7.html)), which differ in how
+/// long a key linked into them survives and which other processes can see it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyringAnchor {
+    /// The calling process's session keyring: lives as long as the login session.
+    Session,
+    /// The calling user's UID keyring: shared by every session for that UID on the system.
+    User,
+    /// The calling user's UID session keyring: shared by sessions started the same way (e.g. the
+    /// same login service), but not by the whole UID.
+    UserSession,
+    /// The calling process's keyring: private to this process, not inherited by children.
+    Process,
+    /// The calling thread's keyring: private to this thread, not shared with other threads.
+    Thread,
+    /// The calling user's persistent keyring: survives logout, expiring only after a period of
+    /// disuse configured by the administrator. Not directly addressable; accessing it requires
+    /// linking it into another keyring first (handled automatically whenever a credential
+    /// anchored here is used, e.g. by [`KeyutilsCredential::get_credential`]).
+    Persistent,
+}
+
+/// Open a handle to `anchor`'s keyring, creating it (or linking it in, for
+/// [`KeyringAnchor::Persistent`]) if necessary. Shared by [`KeyutilsCredential`] and
+/// [`KeyutilsCollection`](crate::collection::KeyutilsCollection).
+///
+/// `linux-keyutils`'s `get_persistent` always fetches and links in the *caller's own* persistent
+/// keyring - there's no cross-UID variant in its public API - so [`KeyringAnchor::Persistent`]
+/// never resolves to anyone else's.
+pub(crate) fn open_anchor(anchor: KeyringAnchor) -> Result<KeyRing> {
+    match anchor {
+        KeyringAnchor::Persistent => {
+            KeyRing::get_persistent(KeyRingIdentifier::Process).map_err(decode_error)
+        }
+        other => KeyRing::from_special_id(other.special_id(), true).map_err(decode_error),
+    }
+}
+
+/// `linux-keyutils`'s `KeyRing::get_persistent` only ever resolves the *caller's own* persistent
+/// keyring - unlike the raw `keyctl_get_persistent(2)` syscall, it has no parameter for
+/// requesting a different UID's - so a `target_uid` can't actually be honored unless it names the
+/// caller's own UID (the no-op case `get_persistent` already supports).
+fn cross_uid_persistent_unsupported(uid: u32) -> ErrorCode {
+    ErrorCode::Invalid(
+        "target_uid".to_string(),
+        format!(
+            "linux-keyutils's get_persistent has no cross-UID variant; \
+             can't fetch uid {uid}'s persistent keyring"
+        ),
+    )
+}
+
+/// The caller's own real UID, as `getuid(2)` reports it - what `target_uid` is compared against
+/// to decide whether it's actually achievable.
+fn real_uid() -> u32 {
+    unsafe { libc::getuid() }
+}
+
+impl KeyringAnchor {
+    pub(crate) fn special_id(self) -> KeyRingIdentifier {
+        match self {
+            KeyringAnchor::Session => KeyRingIdentifier::Session,
+            KeyringAnchor::User => KeyRingIdentifier::User,
+            KeyringAnchor::UserSession => KeyRingIdentifier::UserSession,
+            KeyringAnchor::Process => KeyRingIdentifier::Process,
+            KeyringAnchor::Thread => KeyRingIdentifier::Thread,
+            // The persistent keyring isn't one of the kernel's directly-addressable special
+            // keyrings; it's reached by linking it into one of the others. We use the process
+            // keyring as the anchor point since that's private to us and already exists.
+            KeyringAnchor::Persistent => KeyRingIdentifier::Process,
+        }
+    }
+}
+
+/// The credential itself: a keyutils `description` string together with the special keyring
+/// it's linked into and searched from.
+#[derive(Debug, Clone)]
+pub struct KeyutilsCredential {
+    /// Special keyring this credential is anchored to.
+    pub anchor: KeyringAnchor,
+    /// Entry description: the `keyring:user@service` string (or explicit target) under which
+    /// the key is stored.
+    pub description: String,
+    /// TTL applied to the key by [`set_secret`](CredentialApi::set_secret) and
+    /// [`set_password`](CredentialApi::set_password), via `keyctl_set_timeout`. `None` leaves
+    /// the key to expire only according to its anchor keyring's own rules (e.g. the persistent
+    /// keyring's administrator-configured expiry).
+    pub default_ttl: Option<Duration>,
+    /// Key type to force for this entry's secret. `None` picks `user` or `big_key`
+    /// automatically based on the secret's size; see [`KeyType`].
+    pub key_type: Option<KeyType>,
+    /// For [`KeyringAnchor::Persistent`], the UID whose persistent keyring to use. `None`, or
+    /// `Some` the caller's own real UID, both mean "use the caller's own persistent keyring" (the
+    /// only thing `linux-keyutils`'s `get_persistent` can actually do, having no cross-UID
+    /// variant); any other UID always fails when the credential is used, with a clear error
+    /// rather than silently falling back to the caller's own. Ignored for every other anchor,
+    /// since it was never meaningful there.
+    pub target_uid: Option<u32>,
+}
+
+impl KeyutilsCredential {
+    /// Construct a credential for the given target, service, and user, anchored to `anchor`,
+    /// with no default TTL.
+    ///
+    /// If `target` is `None`, the description is `keyring:user@service`.
+    pub fn new_with_target(
+        target: Option<&str>,
+        service: &str,
+        user: &str,
+        anchor: KeyringAnchor,
+    ) -> Result<Self> {
+        Self::new_with_target_and_ttl(target, service, user, anchor, None)
+    }
+
+    /// Like [`new_with_target`](Self::new_with_target), but also sets a default TTL that's
+    /// applied every time this credential's secret is (re)written.
+    pub fn new_with_target_and_ttl(
+        target: Option<&str>,
+        service: &str,
+        user: &str,
+        anchor: KeyringAnchor,
+        default_ttl: Option<Duration>,
+    ) -> Result<Self> {
+        Self::new_full(target, service, user, anchor, default_ttl, None, None)
+    }
+
+    /// Construct a credential with every available option: a default TTL, a forced [`KeyType`]
+    /// (`None` to auto-select `user` or `big_key` by payload size), and, for
+    /// [`KeyringAnchor::Persistent`], a `target_uid` other than the caller's own.
+    pub fn new_full(
+        target: Option<&str>,
+        service: &str,
+        user: &str,
+        anchor: KeyringAnchor,
+        default_ttl: Option<Duration>,
+        key_type: Option<KeyType>,
+        target_uid: Option<u32>,
+    ) -> Result<Self> {
+        let description = target
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("keyring:{user}@{service}"));
+        Ok(Self {
+            anchor,
+            description,
+            default_ttl,
+            key_type,
+            target_uid,
+        })
+    }
+
+    /// Open a handle to this credential's anchor keyring, creating it (or linking it in, for
+    /// [`KeyringAnchor::Persistent`]) if necessary.
+    fn anchor_keyring(&self) -> Result<KeyRing> {
+        if self.anchor == KeyringAnchor::Persistent {
+            if let Some(uid) = self.target_uid {
+                if uid != real_uid() {
+                    return Err(cross_uid_persistent_unsupported(uid));
+                }
+            }
+        }
+        open_anchor(self.anchor)
+    }
+
+    /// Look up the kernel key backing this credential, along with which [`KeyType`] it is, if
+    /// it's currently present.
+    ///
+    /// Since a credential's backing key type is only known once it's written (or forced via
+    /// [`key_type`](Self::key_type)), this tries `user` first and falls back to `big_key` -
+    /// except for [`KeyringAnchor::Persistent`], where `big_key` can never be present (see
+    /// [`KeyType::BigKey`]), so a missing `user` key is reported as-is rather than masked by a
+    /// `big_key` attempt that could only ever fail with "unsupported".
+    fn locate(&self) -> Result<(Key, KeyType)> {
+        if let Some(key_type) = self.key_type {
+            return self.search_as(key_type).map(|key| (key, key_type));
+        }
+        let user_err = match self.search_as(KeyType::User) {
+            Ok(key) => return Ok((key, KeyType::User)),
+            Err(err) => err,
+        };
+        // Only a missing `user` key is worth trying `big_key` for; anything else (e.g. a
+        // permission error) is a real problem with the `user` key that does exist, and falling
+        // through would risk masking it behind whatever `big_key`'s search turns up instead.
+        if self.anchor == KeyringAnchor::Persistent || !matches!(user_err, ErrorCode::NoEntry) {
+            return Err(user_err);
+        }
+        self.search_as(KeyType::BigKey)
+            .map(|key| (key, KeyType::BigKey))
+    }
+
+    fn search_as(&self, key_type: KeyType) -> Result<Key> {
+        match key_type {
+            KeyType::User => self
+                .anchor_keyring()?
+                .search(&self.description)
+                .map_err(decode_error),
+            KeyType::BigKey => {
+                let target = big_key_target(self.anchor)?;
+                search_of_type(target, RawKeyType::BigKey, &self.description)
+                    .map_err(decode_error)
+            }
+        }
+    }
+
+    /// Look up the kernel key backing this credential, if it's currently present.
+    pub fn get_credential(&self) -> Result<Key> {
+        self.locate().map(|(key, _)| key)
+    }
+
+    /// Which [`KeyType`] currently backs this credential's secret.
+    pub fn current_key_type(&self) -> Result<KeyType> {
+        self.locate().map(|(_, key_type)| key_type)
+    }
+
+    /// Write `secret`, then set its expiration timeout to `ttl`, overriding
+    /// [`default_ttl`](Self::default_ttl) for this write.
+    pub fn set_secret_with_ttl(&self, secret: &[u8], ttl: Duration) -> Result<()> {
+        let key = self.write_secret(secret)?;
+        key.set_timeout(timeout_secs(ttl)).map_err(decode_error)
+    }
+
+    fn write_secret(&self, secret: &[u8]) -> Result<Key> {
+        if secret.len() > BIG_KEY_MAX_SIZE {
+            return Err(ErrorCode::TooLong(
+                "secret".to_string(),
+                BIG_KEY_MAX_SIZE as u32,
+            ));
+        }
+        let key_type = self.key_type.unwrap_or_else(|| KeyType::for_secret(secret));
+        let key = match key_type {
+            KeyType::User => self
+                .anchor_keyring()?
+                .add_key(&self.description, secret)
+                .map_err(decode_error)?,
+            KeyType::BigKey => {
+                let target = big_key_target(self.anchor)?;
+                add_key_of_type(target, RawKeyType::BigKey, &self.description, secret)
+                    .map_err(decode_error)?
+            }
+        };
+        // `add_key` only overwrites a stale key of the *same* type; without this, switching a
+        // description between a small and a large secret (or forcing `key_type`) would leave an
+        // orphaned copy of the old secret linked under the same description.
+        self.cleanup_other_type(key_type)?;
+        Ok(key)
+    }
+
+    /// Unlink any stale key of `written`'s *other* type, left over from this description having
+    /// previously been written at a different size. No-op if there's nothing to clean up.
+    fn cleanup_other_type(&self, written: KeyType) -> Result<()> {
+        let other = match written {
+            KeyType::User => KeyType::BigKey,
+            KeyType::BigKey => KeyType::User,
+        };
+        // big_key can never have been written to a Persistent-anchored credential (see
+        // `big_key_target`), so there's nothing to search for or clean up there.
+        if other == KeyType::BigKey && self.anchor == KeyringAnchor::Persistent {
+            return Ok(());
+        }
+        match self.search_as(other) {
+            Ok(stale) => self
+                .anchor_keyring()?
+                .unlink_key(stale)
+                .map_err(decode_error),
+            Err(ErrorCode::NoEntry) => Ok(()),
+            Err(other) => Err(other),
+        }
+    }
+
+    /// Get the password, refreshing it if it's expired or missing.
+    ///
+    /// This is the "secure cache" pattern from the module docs, built in: if the key has expired
+    /// (or was never there), `refresh` is called to obtain a fresh secret, which is written back
+    /// with [`default_ttl`](Self::default_ttl) applied and then returned. If the key is still
+    /// live, its current value is returned without calling `refresh` at all.
+    pub fn get_password_or_refresh(
+        &self,
+        refresh: impl FnOnce() -> Result<String>,
+    ) -> Result<String> {
+        match self.get_password() {
+            Ok(password) => Ok(password),
+            Err(ErrorCode::NoEntry) => {
+                let password = refresh()?;
+                self.set_password(&password)?;
+                Ok(password)
+            }
+            Err(other) => Err(other),
+        }
+    }
+}
+
+impl CredentialApi for KeyutilsCredential {
+    fn set_password(&self, password: &str) -> Result<()> {
+        self.set_secret(password.as_bytes())
+    }
+
+    fn set_secret(&self, secret: &[u8]) -> Result<()> {
+        let key = self.write_secret(secret)?;
+        if let Some(ttl) = self.default_ttl {
+            key.set_timeout(timeout_secs(ttl)).map_err(decode_error)?;
+        }
+        Ok(())
+    }
+
+    fn get_password(&self) -> Result<String> {
+        let secret = self.get_secret()?;
+        String::from_utf8(secret).map_err(|e| ErrorCode::BadEncoding(e.into_bytes()))
+    }
+
+    fn get_secret(&self) -> Result<Vec<u8>> {
+        let (key, key_type) = self.locate()?;
+        // Sized to this entry's actual key type rather than a single fixed cap: `Key::read_to_vec`
+        // would do here, but it hardcodes a 64KiB buffer and would silently truncate any
+        // `big_key` payload larger than that.
+        let cap = match key_type {
+            KeyType::User => USER_KEY_MAX_SIZE,
+            KeyType::BigKey => BIG_KEY_MAX_SIZE,
+        };
+        let mut buffer = vec![0u8; cap];
+        let len = key.read(&mut buffer).map_err(decode_error)?;
+        buffer.truncate(len);
+        Ok(buffer)
+    }
+
+    fn get_attributes(&self) -> Result<HashMap<String, String>> {
+        // keyutils entries carry no attributes beyond their description.
+        Ok(HashMap::new())
+    }
+
+    fn update_attributes(&self, _: &HashMap<&str, &str>) -> Result<()> {
+        // keyutils entries carry no attributes beyond their description.
+        Ok(())
+    }
+
+    fn delete_credential(&self) -> Result<()> {
+        let (key, _) = self.locate()?;
+        key.invalidate().map_err(|err| match err {
+            KeyError::KeyDoesNotExist => ErrorCode::NoEntry,
+            other => decode_error(other),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl From<KeyutilsCredential> for Box<Credential> {
+    fn from(credential: KeyutilsCredential) -> Self {
+        Box::new(credential)
+    }
+}
@@ -0,0 +1,131 @@
+use keyring::{Error as ErrorCode, Result};
+use linux_keyutils::{Key, KeyError, KeyRing, KeySerialId, KeyType, LinkNode, Links};
+
+use crate::credentials::KeyringAnchor;
+
+/// `KEYCTL_READ`'s kernel opcode (`man 2 keyctl`), hardcoded for the same reason
+/// `KEYCTL_SEARCH` is in `credentials.rs`: `linux-keyutils`'s own opcode enum isn't public.
+const KEYCTL_READ: libc::c_int = 11;
+
+/// Only used as a last resort for [`KeyringAnchor::Persistent`] - see [`read_links`] - since
+/// every other anchor can be read exactly via [`raw_links`] instead.
+///
+/// `get_links(max)` allocates a `Vec<KeySerialId>` of capacity `max`, but then (a pre-existing
+/// quirk of `linux-keyutils` itself) passes that same `max` straight through as the *byte* length
+/// it tells the kernel the buffer holds, even though the buffer is actually `max *
+/// size_of::<KeySerialId>()` bytes - four times bigger. Passing `MAX_KEYRING_LINKS` as `max`
+/// therefore leaves four times that many bytes of real headroom, so the kernel would have to
+/// report a link table that grew past `4 * MAX_KEYRING_LINKS` bytes between our call and the
+/// syscall actually running for this to misbehave - already implausible at this size - and we
+/// still treat a completely-full result as an error rather than silently handing back a partial
+/// list as if it were complete.
+const MAX_KEYRING_LINKS: usize = 65_536;
+
+/// Read every raw serial number linked into the keyring `id`, retrying with a bigger buffer if
+/// the kernel reports more entries than we gave it room for.
+///
+/// This exists instead of `linux-keyutils`'s own `KeyRing::get_links` because that method has a
+/// real soundness bug: it trusts whatever byte count the kernel hands back and calls
+/// `Vec::set_len` against it without ever checking that count against the buffer it actually
+/// allocated, so a single too-small guess is enough to call `set_len` past the Vec's real
+/// capacity - undefined behavior, not merely truncated data. There's no sound way to pick that
+/// one guess up front: a link can be added to `id` between when we learn the current size and
+/// when a read actually runs, no matter how that size was learned.
+///
+/// This loop avoids the bug instead of gambling on a big-enough guess, using the same guarantee
+/// the kernel documents for `KEYCTL_READ` (and that a zero-length first call here relies on
+/// too): a buffer too small for the real link table is left completely untouched, and the
+/// syscall's return value is always the table's true current size, never capped to the buffer.
+/// So a `bytes > buflen` result can only mean "too small, and nothing was written" - safe to grow
+/// and retry - never "partially written". The loop is bounded so a keyring under constant,
+/// pathological concurrent growth fails loudly instead of spinning forever.
+fn raw_links(id: libc::c_ulong) -> std::result::Result<Vec<KeySerialId>, KeyError> {
+    const MAX_ATTEMPTS: u32 = 8;
+    let mut buflen = 0usize;
+    for _ in 0..MAX_ATTEMPTS {
+        let mut buffer = Vec::<KeySerialId>::with_capacity(buflen / std::mem::size_of::<KeySerialId>());
+        let bytes = unsafe {
+            libc::syscall(libc::SYS_keyctl, KEYCTL_READ, id, buffer.as_mut_ptr() as *mut u8, buflen)
+        };
+        if bytes < 0 {
+            return Err(KeyError::from_errno());
+        }
+        let bytes = bytes as usize;
+        if bytes > buflen {
+            // Too small (or, on the first pass, deliberately empty just to learn the size) -
+            // the kernel left the buffer untouched, so it's safe to grow and try again.
+            buflen = bytes;
+            continue;
+        }
+        // SAFETY: `bytes` is the kernel's own count of the bytes it just wrote into `buffer`,
+        // which has `buflen >= bytes` capacity bytes (checked above), so this is in bounds.
+        unsafe { buffer.set_len(bytes / std::mem::size_of::<KeySerialId>()) };
+        return Ok(buffer);
+    }
+    Err(KeyError::OperationNotSupported)
+}
+
+/// Fetch every `user`/`big_key` key directly linked into `keyring`, the special keyring `anchor`
+/// resolves to - the only kinds of link this crate's own credentials and collections ever create
+/// (see [`KeyutilsCollection`](crate::KeyutilsCollection)'s docs on why it has no nested
+/// keyrings of its own). A linked child *keyring* - not something this crate creates, but
+/// something another process sharing the anchor might have - is skipped rather than returned:
+/// unlike a key, a keyring has no public way to be reconstructed from a raw id outside of
+/// `linux-keyutils`'s own special-identifier constructors, so there's nothing we could hand back
+/// for it even if we wanted to.
+///
+/// For every anchor except [`KeyringAnchor::Persistent`], this reads the link table directly via
+/// [`raw_links`], which is race-free by construction (see its docs) rather than merely sized from
+/// a stale guess. [`KeyringAnchor::Persistent`]'s `KeyRing` comes from `linux-keyutils`'s
+/// `get_persistent`, which never exposes its own serial id, so there's no id to read exactly with;
+/// that one case falls back to [`MAX_KEYRING_LINKS`] and `linux-keyutils`'s own `get_links` (which
+/// *can* return linked child keyrings), erroring out instead of silently truncating if it ever
+/// comes back completely full.
+pub fn read_links(anchor: KeyringAnchor, keyring: &KeyRing) -> Result<Links> {
+    if anchor == KeyringAnchor::Persistent {
+        let links = keyring.get_links(MAX_KEYRING_LINKS).map_err(decode_error)?;
+        if links.len() >= MAX_KEYRING_LINKS {
+            return Err(ErrorCode::TooLong(
+                "keyring link count".to_string(),
+                MAX_KEYRING_LINKS as u32,
+            ));
+        }
+        return Ok(links);
+    }
+    let ids = raw_links(anchor.special_id() as libc::c_ulong).map_err(decode_error)?;
+    let nodes = ids
+        .into_iter()
+        .filter_map(|id| {
+            let key = Key::from_id(id);
+            match key.metadata().ok()?.get_type() {
+                KeyType::User | KeyType::BigKey => Some(LinkNode::Key(key)),
+                _ => None,
+            }
+        })
+        .collect();
+    Ok(Links::new(nodes))
+}
+
+/// Translate a [`KeyError`] returned by the kernel's key management syscalls into
+/// the [`keyring::Error`] that this crate's [`CredentialApi`](keyring::credential::CredentialApi)
+/// implementations are required to return.
+///
+/// The kernel reports "this key isn't here (any more)" in several different ways depending
+/// on *why* it isn't there, so [`KeyError::KeyDoesNotExist`], [`KeyError::KeyRevoked`], and
+/// [`KeyError::KeyExpired`] are all folded into [`ErrorCode::NoEntry`]: callers shouldn't have
+/// to care which of those applies before falling back to re-creating the credential.
+pub fn decode_error(err: KeyError) -> ErrorCode {
+    match err {
+        KeyError::KeyDoesNotExist | KeyError::KeyRevoked | KeyError::KeyExpired => {
+            ErrorCode::NoEntry
+        }
+        KeyError::AccessDenied | KeyError::PermissionDenied => {
+            ErrorCode::NoStorageAccess(wrap(err))
+        }
+        other => ErrorCode::PlatformFailure(wrap(other)),
+    }
+}
+
+fn wrap(err: KeyError) -> Box<dyn std::error::Error + Send + Sync> {
+    Box::new(err)
+}
@@ -101,14 +101,30 @@ proc/sys/kernel/keys/persistent_keyring_expiry
 | Expired                  | Logged Out            | Expired        |
 
 **Note**: As mentioned above, a reboot clears all keyrings.
+
+# Choosing a Keyring
+
+The table above describes the default behavior, which links each entry into the user's
+persistent keyring (for logout-surviving storage) and session keyring (for session-lifetime
+access). Some applications need a different lifetime: a systemd service that should only ever
+see credentials for the current login session, or a short-lived helper that shouldn't leak its
+secret to child processes. [`KeyutilsCredentialBuilder::with_anchor`] lets you pick which
+special keyring ([`KeyringAnchor`]) an entry is linked into and searched from instead of the
+default.
 */
 mod error;
 
 mod credentials;
-pub use credentials::KeyutilsCredential;
+pub use credentials::{KeyType, KeyringAnchor, KeyutilsCredential};
 
 mod builder;
 pub use builder::KeyutilsCredentialBuilder;
 
+mod collection;
+pub use collection::KeyutilsCollection;
+
+mod search;
+pub use search::{search, KeyutilsEntry};
+
 #[cfg(test)]
 mod tests;
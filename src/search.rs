@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+
+use keyring::{Error as ErrorCode, Result};
+
+use crate::credentials::{open_anchor, KeyringAnchor};
+use crate::error::read_links;
+
+/// The keyrings walked by [`search`], in the kernel's default search order: thread, then
+/// process, then session, then (if the session has no parent) the user's session and user
+/// keyrings. The persistent keyring isn't part of the kernel's implicit search order - it has
+/// to be linked in explicitly - but we include it anyway so callers can reconcile state after a
+/// partial persistent-keyring expiry.
+const SEARCH_ORDER: [KeyringAnchor; 6] = [
+    KeyringAnchor::Thread,
+    KeyringAnchor::Process,
+    KeyringAnchor::Session,
+    KeyringAnchor::UserSession,
+    KeyringAnchor::User,
+    KeyringAnchor::Persistent,
+];
+
+/// One key found by [`search`]: its description and kernel serial number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyutilsEntry {
+    /// The key's `description`, i.e. what a [`KeyutilsCredential`](crate::KeyutilsCredential)
+    /// would call its `target`.
+    pub description: String,
+    /// The kernel's serial number for this key, as used by e.g. `keyctl_read`/`keyctl_revoke`.
+    pub serial: i32,
+}
+
+/// Walk every keyring reachable from the calling thread/process/session/user (in the kernel's
+/// default search order, plus the persistent keyring) and return the descriptions and serial
+/// numbers of the keys found there.
+///
+/// If `prefix` is given, only descriptions starting with it are returned - pass e.g.
+/// `"keyring:alice@"` to find every entry for a given user regardless of service, or
+/// `"keyring:alice@example.com"` for one user/service pair.
+pub fn search(prefix: Option<&str>) -> Result<Vec<KeyutilsEntry>> {
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+    for anchor in SEARCH_ORDER {
+        // A keyring we can't open (doesn't exist yet, or we lack permission) just contributes
+        // no entries; it's not an error for the overall search.
+        let Ok(keyring) = open_anchor(anchor) else {
+            continue;
+        };
+        // As with a keyring we can't open, one that's empty or unreadable just contributes no
+        // entries. But a keyring we *can* read that turns out to hold more links than we can
+        // safely enumerate (see `read_links`) is a real problem the caller needs to know about,
+        // not something to quietly skip - so only those two specific errors are swallowed here.
+        let links = match read_links(anchor, &keyring) {
+            Ok(links) => links,
+            Err(ErrorCode::NoEntry | ErrorCode::NoStorageAccess(_)) => continue,
+            Err(err) => return Err(err),
+        };
+        for key in links.iter().filter_map(|node| node.as_key()) {
+            let serial = key.get_id().as_raw_id();
+            if !seen.insert(serial) {
+                continue;
+            }
+            let description = key
+                .metadata()
+                .map_err(crate::error::decode_error)?
+                .get_description()
+                .to_string();
+            if prefix.is_some_and(|prefix| !description.starts_with(prefix)) {
+                continue;
+            }
+            entries.push(KeyutilsEntry { description, serial });
+        }
+    }
+    Ok(entries)
+}
@@ -0,0 +1,266 @@
+use std::time::Duration;
+
+use keyring::credential::CredentialApi;
+
+use crate::credentials::{KeyringAnchor, KeyType};
+use crate::{search, KeyutilsCollection, KeyutilsCredential};
+
+fn entry_new(service: &str, user: &str) -> KeyutilsCredential {
+    KeyutilsCredential::new_with_target(None, service, user, KeyringAnchor::Session)
+        .expect("should be able to build a credential")
+}
+
+#[test]
+fn test_default_description() {
+    let credential = entry_new("service", "user");
+    assert_eq!(credential.description, "keyring:user@service");
+}
+
+#[test]
+fn test_explicit_target() {
+    let credential =
+        KeyutilsCredential::new_with_target(Some("explicit"), "service", "user", KeyringAnchor::Session)
+            .expect("should be able to build a credential");
+    assert_eq!(credential.description, "explicit");
+}
+
+#[test]
+fn test_missing_entry() {
+    let credential = entry_new("test-missing-entry-service", "test-missing-entry-user");
+    assert!(
+        matches!(credential.get_password(), Err(keyring::Error::NoEntry)),
+        "Missing entry should error NoEntry"
+    );
+}
+
+#[test]
+fn test_empty_password() {
+    let credential = entry_new("test-empty-password-service", "test-empty-password-user");
+    credential
+        .set_password("")
+        .expect("Couldn't set empty password");
+    let password = credential.get_password().expect("Couldn't get empty password");
+    assert_eq!(password, "", "Retrieved password doesn't match empty one");
+    credential
+        .delete_credential()
+        .expect("Couldn't delete credential");
+}
+
+#[test]
+fn test_get_password_or_refresh() {
+    let credential = entry_new(
+        "test-get-password-or-refresh-service",
+        "test-get-password-or-refresh-user",
+    );
+    // No entry yet, so the closure should be invoked and its result stored.
+    let password = credential
+        .get_password_or_refresh(|| Ok("refreshed password".to_string()))
+        .expect("refresh should supply a password when none is cached");
+    assert_eq!(password, "refreshed password");
+    // Now that it's cached, the closure should not be invoked again.
+    let password = credential
+        .get_password_or_refresh(|| panic!("refresh shouldn't be called for a live entry"))
+        .expect("cached password should be returned without refreshing");
+    assert_eq!(password, "refreshed password");
+    credential
+        .delete_credential()
+        .expect("Couldn't delete credential");
+}
+
+#[test]
+fn test_large_secret_uses_big_key() {
+    // A payload over the user-key size limit should be stored as a big_key instead of being
+    // rejected or silently truncated.
+    let credential = entry_new("test-large-secret-service", "test-large-secret-user");
+    let secret = vec![0u8; 64 * 1024];
+    credential
+        .set_secret(&secret)
+        .expect("Couldn't set large secret");
+    assert_eq!(
+        credential
+            .current_key_type()
+            .expect("should find the key"),
+        KeyType::BigKey,
+        "A payload over the user-key size limit should be stored as a big_key"
+    );
+    assert_eq!(
+        credential.get_secret().expect("Couldn't get large secret"),
+        secret
+    );
+    credential
+        .delete_credential()
+        .expect("Couldn't delete credential");
+}
+
+#[test]
+fn test_oversized_secret_is_rejected() {
+    // Even big_key has a hard cap; past that, the secret must be rejected clearly rather than
+    // failing confusingly at the syscall.
+    let credential = entry_new("test-oversized-secret-service", "test-oversized-secret-user");
+    let secret = vec![0u8; 2 * 1024 * 1024];
+    assert!(
+        matches!(credential.set_secret(&secret), Err(keyring::Error::TooLong(_, _))),
+        "A payload over the big_key size limit should be rejected, not silently truncated or stored"
+    );
+}
+
+#[test]
+fn test_switching_size_cleans_up_stale_key() {
+    // Writing a small secret, then a large one, under the same description shouldn't leave the
+    // old user-type key linked in alongside the new big_key one.
+    let credential = entry_new(
+        "test-switch-key-type-service",
+        "test-switch-key-type-user",
+    );
+    credential
+        .set_password("short")
+        .expect("Couldn't set short secret");
+    let large_secret = vec![1u8; 64 * 1024];
+    credential
+        .set_secret(&large_secret)
+        .expect("Couldn't set large secret");
+    assert_eq!(
+        credential
+            .current_key_type()
+            .expect("should find the key"),
+        KeyType::BigKey
+    );
+    assert_eq!(
+        credential.get_secret().expect("Couldn't get secret"),
+        large_secret,
+        "should read back the large secret, not a stale leftover user-type key"
+    );
+    credential
+        .delete_credential()
+        .expect("Couldn't delete credential");
+    assert!(
+        matches!(credential.get_password(), Err(keyring::Error::NoEntry)),
+        "deleting the credential should leave no key of either type behind"
+    );
+}
+
+#[test]
+fn test_sub_second_ttl_still_expires() {
+    // A sub-second TTL must round up to 1 second rather than being passed straight through to
+    // set_timeout, since set_timeout(0) clears the timeout entirely instead of expiring the key
+    // immediately.
+    let credential = entry_new("test-sub-second-ttl-service", "test-sub-second-ttl-user");
+    credential
+        .set_secret_with_ttl(b"short-lived", Duration::from_millis(100))
+        .expect("Couldn't set secret with sub-second ttl");
+    std::thread::sleep(Duration::from_millis(1100));
+    assert!(
+        matches!(credential.get_password(), Err(keyring::Error::NoEntry)),
+        "A sub-second ttl should still expire the key, not disable its timeout entirely"
+    );
+}
+
+#[test]
+fn test_collection_round_trip() {
+    let collection = KeyutilsCollection::new(KeyringAnchor::Session, "test-collection")
+        .expect("should be able to open a collection");
+    collection
+        .add("realm-a", b"ticket-a")
+        .expect("Couldn't add entry to collection");
+    collection
+        .add("realm-b", b"ticket-b")
+        .expect("Couldn't add entry to collection");
+    assert_eq!(
+        collection.get("realm-a").expect("Couldn't get entry"),
+        b"ticket-a"
+    );
+    let mut entries = collection.entries().expect("Couldn't list entries");
+    entries.sort();
+    assert_eq!(entries, vec!["realm-a".to_string(), "realm-b".to_string()]);
+    collection
+        .remove("realm-a")
+        .expect("Couldn't remove entry");
+    assert_eq!(
+        collection.entries().expect("Couldn't list entries"),
+        vec!["realm-b".to_string()]
+    );
+    collection.delete().expect("Couldn't delete collection");
+}
+
+#[test]
+fn test_search_by_prefix() {
+    let credential = entry_new("test-search-service", "test-search-user");
+    credential
+        .set_password("findable")
+        .expect("Couldn't set password");
+    let found = search(Some("keyring:test-search-user@")).expect("search should succeed");
+    assert!(
+        found.iter().any(|entry| entry.description == credential.description),
+        "search should find the entry we just created"
+    );
+    let none = search(Some("keyring:no-such-user@")).expect("search should succeed");
+    assert!(
+        none.iter().all(|entry| !entry.description.starts_with("keyring:no-such-user@")),
+        "search should not find entries for an unused prefix"
+    );
+    credential
+        .delete_credential()
+        .expect("Couldn't delete credential");
+}
+
+#[test]
+fn test_cross_uid_persistent_without_cap_setuid_is_denied() {
+    // `linux-keyutils`'s `get_persistent` has no cross-UID variant at all, so asking for some
+    // other user's persistent keyring should fail clearly rather than silently falling back to
+    // our own.
+    let other_uid = unsafe { libc::getuid() } + 1;
+    let credential = KeyutilsCredential::new_full(
+        None,
+        "test-cross-uid-service",
+        "test-cross-uid-user",
+        KeyringAnchor::Persistent,
+        None,
+        None,
+        Some(other_uid),
+    )
+    .expect("should be able to build a credential");
+    assert!(
+        matches!(credential.get_password(), Err(keyring::Error::Invalid(_, _))),
+        "Cross-UID persistent keyring access should be rejected up front, since \
+         linux-keyutils's get_persistent has no cross-UID variant to even attempt it with"
+    );
+}
+
+#[test]
+fn test_own_uid_persistent_target_is_a_no_op() {
+    // A target_uid equal to the caller's own real UID is the one case get_persistent can
+    // actually satisfy (it's just the caller's own persistent keyring), so it shouldn't be
+    // rejected the way a genuinely different UID is.
+    let own_uid = unsafe { libc::getuid() };
+    let credential = KeyutilsCredential::new_full(
+        None,
+        "test-own-uid-persistent-service",
+        "test-own-uid-persistent-user",
+        KeyringAnchor::Persistent,
+        None,
+        None,
+        Some(own_uid),
+    )
+    .expect("should be able to build a credential");
+    credential
+        .set_password("own-uid-secret")
+        .expect("own UID should be usable as a target_uid, not rejected");
+    assert_eq!(
+        credential.get_password().expect("Couldn't get password"),
+        "own-uid-secret"
+    );
+    credential
+        .delete_credential()
+        .expect("Couldn't delete credential");
+}
+
+#[test]
+fn test_round_trip() {
+    let credential = entry_new("test-round-trip-service", "test-round-trip-user");
+    let password = "test round trip password";
+    credential.set_password(password).expect("Couldn't set password");
+    let stored = credential.get_password().expect("Couldn't get password");
+    assert_eq!(stored, password);
+    credential.delete_credential().expect("Couldn't delete credential");
+    assert!(matches!(credential.get_password(), Err(keyring::Error::NoEntry)));
+}